@@ -11,6 +11,19 @@ pub enum FileSystemError {
     ReadError(String),
     /// An error occurred during a write operation.
     WriteError(String),
+    /// Expected a directory at the given path, but found something else
+    /// (or a path component along the way was not a directory). Also
+    /// returned by file operations such as `read` and `metadata` when the
+    /// path names a directory.
+    NotADirectory(String),
+    /// An entry already exists at the given path.
+    AlreadyExists(String),
+    /// A write was attempted on a read-only file.
+    PermissionDenied(String),
+    /// A snapshot record failed its checksum during restore.
+    Corrupted(String),
+    /// Following a chain of symbolic links exceeded the depth limit.
+    TooManyLinks(String),
 }
 
 impl fmt::Display for FileSystemError {
@@ -28,6 +41,21 @@ impl fmt::Display for FileSystemError {
             FileSystemError::WriteError(msg) => {
                 write!(f, "Write error: {msg}")
             }
+            FileSystemError::NotADirectory(path) => {
+                write!(f, "Not a directory: {path}")
+            }
+            FileSystemError::AlreadyExists(path) => {
+                write!(f, "Already exists: {path}")
+            }
+            FileSystemError::PermissionDenied(path) => {
+                write!(f, "Permission denied: {path}")
+            }
+            FileSystemError::Corrupted(path) => {
+                write!(f, "Corrupted snapshot record: {path}")
+            }
+            FileSystemError::TooManyLinks(path) => {
+                write!(f, "Too many levels of symbolic links: {path}")
+            }
         }
     }
 }
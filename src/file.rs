@@ -1,13 +1,31 @@
+use std::time::SystemTime;
+
+use crate::metadata::{Metadata, Permissions};
+
 #[derive(Debug, Clone)]
 pub struct File {
     data: Vec<u8>,
+    created: SystemTime,
+    modified: SystemTime,
+    permissions: Permissions,
 }
 
 impl File {
-    pub(crate) fn new() -> Self { Self { data: Vec::new() } }
+    pub(crate) fn new() -> Self {
+        let now = SystemTime::now();
+        Self {
+            data: Vec::new(),
+            created: now,
+            modified: now,
+            permissions: Permissions::default(),
+        }
+    }
 
     /// Writes data to the file at the specified offset.
     ///
+    /// Updates the file's `modified` timestamp. The caller is responsible
+    /// for rejecting writes to a read-only file before calling this.
+    ///
     /// # Safety
     /// The caller must ensure that `offset + data.len()` does not overflow.
     pub(crate) fn write(&mut self, offset: usize, data: &[u8]) {
@@ -22,6 +40,7 @@ impl File {
         }
 
         self.data[offset..end_pos].copy_from_slice(data);
+        self.modified = SystemTime::now();
     }
 
     /// Reads data from the file starting at the specified offset.
@@ -33,4 +52,32 @@ impl File {
         let end_pos = std::cmp::min(offset + len, self.data.len());
         self.data[offset..end_pos].to_vec()
     }
+
+    /// Returns the current size of the file, in bytes.
+    pub(crate) fn len(&self) -> usize { self.data.len() }
+
+    /// Returns the file's full contents.
+    pub(crate) fn as_slice(&self) -> &[u8] { &self.data }
+
+    /// Clears the file's contents and updates its `modified` timestamp.
+    pub(crate) fn truncate(&mut self) {
+        self.data.clear();
+        self.modified = SystemTime::now();
+    }
+
+    /// Returns a snapshot of the file's metadata.
+    pub(crate) fn metadata(&self) -> Metadata {
+        Metadata {
+            len: self.data.len() as u64,
+            created: self.created,
+            modified: self.modified,
+            permissions: self.permissions,
+        }
+    }
+
+    /// Returns the file's current permissions.
+    pub(crate) fn permissions(&self) -> Permissions { self.permissions }
+
+    /// Sets the file's permissions.
+    pub(crate) fn set_permissions(&mut self, permissions: Permissions) { self.permissions = permissions; }
 }
@@ -0,0 +1,253 @@
+use std::{
+    future::Future,
+    io,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use tokio::{
+    io::{AsyncRead, AsyncSeek, AsyncWrite, ReadBuf},
+    sync::RwLock,
+};
+
+use crate::file::File;
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+
+/// Options used to configure how a file is opened, mirroring
+/// [`tokio::fs::OpenOptions`].
+///
+/// # Examples
+///
+/// ```rust
+/// # tokio_test::block_on(async {
+/// use memfs::OpenOptions;
+///
+/// let fs = memfs::FileSystem::new();
+/// let mut handle = fs.open("/file.txt", OpenOptions::new().write(true).create(true)).await.unwrap();
+/// # });
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OpenOptions {
+    read: bool,
+    write: bool,
+    append: bool,
+    create: bool,
+    truncate: bool,
+}
+
+impl OpenOptions {
+    /// Creates a blank set of options, with every flag unset.
+    pub fn new() -> Self { Self::default() }
+
+    /// Sets the option to allow reading through the resulting handle.
+    pub fn read(mut self, read: bool) -> Self {
+        self.read = read;
+        self
+    }
+
+    /// Sets the option to allow writing through the resulting handle.
+    pub fn write(mut self, write: bool) -> Self {
+        self.write = write;
+        self
+    }
+
+    /// Sets the option to force every write to the end of the file,
+    /// regardless of the handle's current cursor position.
+    pub fn append(mut self, append: bool) -> Self {
+        self.append = append;
+        self
+    }
+
+    /// Sets the option to create the file if it doesn't already exist.
+    pub fn create(mut self, create: bool) -> Self {
+        self.create = create;
+        self
+    }
+
+    /// Sets the option to truncate the file to zero length once opened.
+    pub fn truncate(mut self, truncate: bool) -> Self {
+        self.truncate = truncate;
+        self
+    }
+
+    pub(crate) fn should_create(&self) -> bool { self.create }
+
+    pub(crate) fn should_truncate(&self) -> bool { self.truncate }
+
+    pub(crate) fn wants_write(&self) -> bool { self.write || self.append }
+}
+
+enum ReadState {
+    Idle,
+    Pending(BoxFuture<Vec<u8>>),
+}
+
+enum WriteState {
+    Idle,
+    Pending(BoxFuture<(usize, usize)>),
+}
+
+enum SeekState {
+    Idle,
+    Start(io::SeekFrom),
+    Pending(BoxFuture<io::Result<usize>>),
+}
+
+/// A handle to an open file, implementing [`tokio::io::AsyncRead`],
+/// [`tokio::io::AsyncWrite`] and [`tokio::io::AsyncSeek`] just like
+/// [`tokio::fs::File`].
+///
+/// Obtained via [`crate::FileSystem::open`].
+pub struct FileHandle {
+    file: Arc<RwLock<File>>,
+    position: u64,
+    read: bool,
+    write: bool,
+    append: bool,
+    read_state: ReadState,
+    write_state: WriteState,
+    seek_state: SeekState,
+}
+
+impl FileHandle {
+    pub(crate) async fn new(file: Arc<RwLock<File>>, options: &OpenOptions) -> Self {
+        let position = if options.append { file.read().await.len() as u64 } else { 0 };
+
+        Self {
+            file,
+            position,
+            read: options.read,
+            write: options.write,
+            append: options.append,
+            read_state: ReadState::Idle,
+            write_state: WriteState::Idle,
+            seek_state: SeekState::Idle,
+        }
+    }
+}
+
+impl AsyncRead for FileHandle {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if !this.read {
+            return Poll::Ready(Err(io::Error::new(io::ErrorKind::PermissionDenied, "file not opened for reading")));
+        }
+        loop {
+            match &mut this.read_state {
+                ReadState::Idle => {
+                    let file = this.file.clone();
+                    let offset = this.position as usize;
+                    let len = buf.remaining();
+                    this.read_state = ReadState::Pending(Box::pin(async move {
+                        let file = file.read().await;
+                        file.read(offset, len)
+                    }));
+                }
+                ReadState::Pending(fut) => {
+                    let data = match fut.as_mut().poll(cx) {
+                        Poll::Ready(data) => data,
+                        Poll::Pending => return Poll::Pending,
+                    };
+                    this.read_state = ReadState::Idle;
+                    this.position += data.len() as u64;
+                    buf.put_slice(&data);
+                    return Poll::Ready(Ok(()));
+                }
+            }
+        }
+    }
+}
+
+impl AsyncWrite for FileHandle {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, data: &[u8]) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if !this.write && !this.append {
+            return Poll::Ready(Err(io::Error::new(io::ErrorKind::PermissionDenied, "file not opened for writing")));
+        }
+        loop {
+            match &mut this.write_state {
+                WriteState::Idle => {
+                    let file = this.file.clone();
+                    let position = this.position as usize;
+                    let append = this.append;
+                    let data = data.to_vec();
+                    let len = data.len();
+                    this.write_state = WriteState::Pending(Box::pin(async move {
+                        let mut file = file.write().await;
+                        let offset = if append { file.len() } else { position };
+                        file.write(offset, &data);
+                        (offset, len)
+                    }));
+                }
+                WriteState::Pending(fut) => {
+                    let (offset, len) = match fut.as_mut().poll(cx) {
+                        Poll::Ready(result) => result,
+                        Poll::Pending => return Poll::Pending,
+                    };
+                    this.write_state = WriteState::Idle;
+                    this.position = (offset + len) as u64;
+                    return Poll::Ready(Ok(len));
+                }
+            }
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> { Poll::Ready(Ok(())) }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> { Poll::Ready(Ok(())) }
+}
+
+impl AsyncSeek for FileHandle {
+    fn start_seek(self: Pin<&mut Self>, position: io::SeekFrom) -> io::Result<()> {
+        self.get_mut().seek_state = SeekState::Start(position);
+        Ok(())
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        let this = self.get_mut();
+        loop {
+            match &mut this.seek_state {
+                SeekState::Idle => return Poll::Ready(Ok(this.position)),
+                SeekState::Start(io::SeekFrom::Start(offset)) => {
+                    this.position = *offset;
+                    this.seek_state = SeekState::Idle;
+                }
+                SeekState::Start(io::SeekFrom::Current(delta)) => {
+                    this.position = apply_delta(this.position, *delta)?;
+                    this.seek_state = SeekState::Idle;
+                }
+                SeekState::Start(io::SeekFrom::End(delta)) => {
+                    let file = this.file.clone();
+                    let delta = *delta;
+                    this.seek_state = SeekState::Pending(Box::pin(async move {
+                        let len = file.read().await.len();
+                        apply_delta(len as u64, delta).map(|pos| pos as usize)
+                    }));
+                }
+                SeekState::Pending(fut) => {
+                    let position = match fut.as_mut().poll(cx) {
+                        Poll::Ready(position) => position?,
+                        Poll::Pending => return Poll::Pending,
+                    };
+                    this.position = position as u64;
+                    this.seek_state = SeekState::Idle;
+                    return Poll::Ready(Ok(this.position));
+                }
+            }
+        }
+    }
+}
+
+/// Applies a signed offset to an unsigned position, erroring like
+/// `std::io::Seek` does on underflow.
+fn apply_delta(position: u64, delta: i64) -> io::Result<u64> {
+    let result = if delta >= 0 {
+        position.checked_add(delta as u64)
+    } else {
+        position.checked_sub(delta.unsigned_abs())
+    };
+
+    result.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid seek to a negative or overflowing position"))
+}
@@ -1,18 +1,43 @@
-use std::{collections::HashMap, sync::Arc};
+use std::sync::Arc;
 
 use error::FileSystemError;
 use file::File;
-use tokio::sync::RwLock;
+use futures_core::Stream;
+use futures_util::StreamExt;
+pub use handle::{FileHandle, OpenOptions};
+pub use metadata::{Metadata, Permissions};
+use node::{Dir, Node};
+use path::split_path;
+use regex::RegexBuilder;
+pub use search::{Pattern, SearchMatch, SearchMode, SearchQuery};
+pub use snapshot::RestoreMode;
+use stream::FileStream;
+use tokio::sync::{broadcast, RwLock};
+use tokio_stream::wrappers::BroadcastStream;
+pub use watch::{ChangeEvent, ChangeKind, ChangeKindSet};
 
 pub mod error;
 mod file;
+mod handle;
+mod metadata;
+mod node;
+mod path;
+mod search;
+mod snapshot;
+mod stream;
+mod watch;
+
+/// The capacity of the broadcast channel backing [`FileSystem::watch`].
+const CHANGE_EVENT_CAPACITY: usize = 1024;
+
+/// The maximum number of symlink hops [`follow_symlinks`] will chase before
+/// giving up, mirroring the `ELOOP` limit real file systems enforce.
+const MAX_SYMLINK_DEPTH: usize = 40;
 
 pub type Result<T> = std::result::Result<T, FileSystemError>;
 
-/// An in-memory file system that stores files as byte arrays.
-///
-/// This file system only supports files (no directories) and provides
-/// basic operations for creating, reading, and writing files.
+/// An in-memory file system that stores files as byte arrays, arranged in a
+/// POSIX-like hierarchical namespace of directories and files.
 ///
 /// # Examples
 ///
@@ -36,28 +61,140 @@ pub type Result<T> = std::result::Result<T, FileSystemError>;
 /// ```
 #[derive(Debug)]
 pub struct FileSystem {
-    files: Arc<RwLock<HashMap<String, Arc<RwLock<File>>>>>,
+    root: Arc<RwLock<Dir>>,
+    changes: broadcast::Sender<ChangeEvent>,
+}
+
+/// An entry returned by [`FileSystem::read_dir`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirEntry {
+    /// The entry's name, relative to the directory it was listed from.
+    pub name: String,
+    /// Whether this entry is itself a directory.
+    pub is_dir: bool,
+}
+
+/// Walks `components` starting at `dir`, following only directory nodes.
+///
+/// Returns [`FileSystemError::NotADirectory`] if a component names a file,
+/// and [`FileSystemError::FileNotFound`] if a component doesn't exist.
+fn navigate<'a>(mut dir: &'a Dir, components: &[&str], full_path: &str) -> Result<&'a Dir> {
+    for name in components {
+        match dir.children.get(*name) {
+            Some(Node::Dir(child)) => dir = child,
+            Some(Node::File(_) | Node::Symlink(_)) => {
+                return Err(FileSystemError::NotADirectory(full_path.to_string()))
+            }
+            None => return Err(FileSystemError::FileNotFound(full_path.to_string())),
+        }
+    }
+    Ok(dir)
+}
+
+/// Mutable counterpart of [`navigate`].
+fn navigate_mut<'a>(mut dir: &'a mut Dir, components: &[&str], full_path: &str) -> Result<&'a mut Dir> {
+    for name in components {
+        match dir.children.get_mut(*name) {
+            Some(Node::Dir(child)) => dir = child,
+            Some(Node::File(_) | Node::Symlink(_)) => {
+                return Err(FileSystemError::NotADirectory(full_path.to_string()))
+            }
+            None => return Err(FileSystemError::FileNotFound(full_path.to_string())),
+        }
+    }
+    Ok(dir)
+}
+
+/// Resolves `path`'s final component if it names a symlink, repeatedly
+/// following the chain until a non-symlink entry (or nothing) is reached.
+///
+/// Intermediate directory components are not followed through symlinks; only
+/// the final component of `path` is resolved.
+fn follow_symlinks(root: &Dir, path: &str) -> Result<String> {
+    let mut current = path.to_string();
+
+    for _ in 0..MAX_SYMLINK_DEPTH {
+        let components = split_path(&current)?;
+        let (name, parent_components) = match components.split_last() {
+            Some(split) => split,
+            None => return Ok(current),
+        };
+
+        let parent = navigate(root, parent_components, &current)?;
+        match parent.children.get(*name) {
+            Some(Node::Symlink(target)) => current = target.clone(),
+            _ => return Ok(current),
+        }
+    }
+
+    Err(FileSystemError::TooManyLinks(path.to_string()))
 }
 
 impl FileSystem {
-    /// Creates a new empty file system.
+    /// Creates a new empty file system, containing only the root directory.
     pub fn new() -> Self {
+        let (changes, _) = broadcast::channel(CHANGE_EVENT_CAPACITY);
         Self {
-            files: Arc::new(RwLock::new(HashMap::new())),
+            root: Arc::new(RwLock::new(Dir::new())),
+            changes,
         }
     }
 
-    /// Creates a file at the specified path if it doesn't exist.
+    /// Publishes a [`ChangeEvent`] to any active [`FileSystem::watch`] streams.
     ///
-    /// If the file already exists, this operation does nothing.
+    /// Ignores the case where there are no subscribers.
+    fn notify(&self, path: &str, kind: ChangeKind) {
+        let _ = self.changes.send(ChangeEvent { path: path.to_string(), kind });
+    }
+
+    /// Watches for mutations under `path_prefix`, filtered by `kinds`.
+    ///
+    /// Events are published by [`FileSystem::touch`], [`FileSystem::write`],
+    /// [`FileSystem::remove_file`], [`FileSystem::remove_dir`],
+    /// [`FileSystem::remove_dir_all`], [`FileSystem::rename`] (one `Rename`
+    /// event, at the destination path), [`FileSystem::hard_link`] and
+    /// [`FileSystem::symlink`]. [`FileSystem::create_dir`],
+    /// [`FileSystem::create_dir_all`], [`FileSystem::open`],
+    /// [`FileSystem::set_permissions`] and writes through a [`FileHandle`]
+    /// publish no events. `path_prefix` is matched as a plain string prefix.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// use futures_util::StreamExt;
+    /// use memfs::{ChangeKind, ChangeKindSet};
     ///
-    /// # Arguments
+    /// let fs = memfs::FileSystem::new();
+    /// let mut changes = fs.watch("/", ChangeKindSet::all());
+    ///
+    /// fs.touch("/file.txt").await.unwrap();
+    ///
+    /// let event = changes.next().await.unwrap();
+    /// assert_eq!(event.path, "/file.txt");
+    /// assert_eq!(event.kind, ChangeKind::Create);
+    /// # });
+    /// ```
+    pub fn watch(&self, path_prefix: &str, kinds: ChangeKindSet) -> impl Stream<Item = ChangeEvent> {
+        let prefix = path_prefix.to_string();
+        BroadcastStream::new(self.changes.subscribe()).filter_map(move |event| {
+            let matched = match &event {
+                Ok(event) => kinds.contains(event.kind) && event.path.starts_with(&prefix),
+                Err(_) => false,
+            };
+            std::future::ready(if matched { event.ok() } else { None })
+        })
+    }
+
+    /// Creates a file at the specified path if it doesn't exist.
     ///
-    /// * `path` - The path where the file should be created
+    /// If the file already exists, this operation does nothing.
     ///
     /// # Errors
     ///
     /// Returns [`FileSystemError::InvalidPath`] if the path is invalid.
+    /// Returns [`FileSystemError::NotADirectory`] if a component of the path is not a directory.
+    /// Returns [`FileSystemError::AlreadyExists`] if a directory already exists at the path.
     ///
     /// # Examples
     ///
@@ -68,32 +205,42 @@ impl FileSystem {
     /// # });
     /// ```
     pub async fn touch(&self, path: &str) -> Result<()> {
-        if path.is_empty() {
-            return Err(FileSystemError::InvalidPath("Path cannot be empty".to_string()));
+        let components = split_path(path)?;
+        let (name, parent_components) = components
+            .split_last()
+            .ok_or_else(|| FileSystemError::InvalidPath("Path cannot be the root directory".to_string()))?;
+
+        let mut root = self.root.write().await;
+        let parent = navigate_mut(&mut root, parent_components, path)?;
+
+        match parent.children.get(*name) {
+            Some(Node::Dir(_)) => Err(FileSystemError::AlreadyExists(path.to_string())),
+            Some(Node::File(_) | Node::Symlink(_)) => Ok(()),
+            None => {
+                parent
+                    .children
+                    .insert(name.to_string(), Node::File(Arc::new(RwLock::new(File::new()))));
+                drop(root);
+                self.notify(path, ChangeKind::Create);
+                Ok(())
+            }
         }
-
-        let mut files = self.files.write().await;
-        files
-            .entry(path.to_string())
-            .or_insert_with(|| Arc::new(RwLock::new(File::new())));
-        Ok(())
     }
 
     /// Writes data to a file at the specified offset.
     ///
     /// If the file doesn't exist, it will be created. If the offset is beyond
-    /// the current file size, the file will be extended with zero bytes.
-    ///
-    /// # Arguments
-    ///
-    /// * `path` - The path to the file
-    /// * `offset` - The byte offset where to start writing
-    /// * `data` - The data to write
+    /// the current file size, the file will be extended with zero bytes. If
+    /// `path` names a symlink, it is transparently resolved first.
     ///
     /// # Errors
     ///
     /// Returns [`FileSystemError::InvalidPath`] if the path is empty.
+    /// Returns [`FileSystemError::NotADirectory`] if a component of the path is not a directory.
+    /// Returns [`FileSystemError::AlreadyExists`] if a directory already exists at the path.
     /// Returns [`FileSystemError::WriteError`] if the operation would cause overflow.
+    /// Returns [`FileSystemError::PermissionDenied`] if the file is read-only.
+    /// Returns [`FileSystemError::TooManyLinks`] if resolving a symlink chain exceeds the depth limit.
     ///
     /// # Examples
     ///
@@ -105,26 +252,40 @@ impl FileSystem {
     /// # });
     /// ```
     pub async fn write(&self, path: &str, offset: usize, data: &[u8]) -> Result<()> {
-        if path.is_empty() {
-            return Err(FileSystemError::InvalidPath("Path cannot be empty".to_string()));
-        }
-
         if offset.checked_add(data.len()).is_none() {
             return Err(FileSystemError::WriteError(
                 "Write operation would cause overflow".to_string(),
             ));
         }
 
-        let file_rwlock = {
-            let mut files = self.files.write().await;
-            files
-                .entry(path.to_string())
-                .or_insert_with(|| Arc::new(RwLock::new(File::new())))
-                .clone()
+        let (file_rwlock, created, resolved_path) = {
+            let mut root = self.root.write().await;
+            let resolved_path = follow_symlinks(&root, path)?;
+            let components = split_path(&resolved_path)?;
+            let (name, parent_components) = components
+                .split_last()
+                .ok_or_else(|| FileSystemError::InvalidPath("Path cannot be the root directory".to_string()))?;
+            let parent = navigate_mut(&mut root, parent_components, &resolved_path)?;
+
+            match parent.children.get(*name) {
+                Some(Node::Dir(_)) => return Err(FileSystemError::AlreadyExists(resolved_path)),
+                Some(Node::File(file)) => (file.clone(), false, resolved_path),
+                Some(Node::Symlink(_)) => unreachable!("follow_symlinks already resolved any trailing symlink"),
+                None => {
+                    let file = Arc::new(RwLock::new(File::new()));
+                    parent.children.insert(name.to_string(), Node::File(file.clone()));
+                    (file, true, resolved_path)
+                }
+            }
         };
 
         let mut file = file_rwlock.write().await;
+        if file.permissions().readonly() {
+            return Err(FileSystemError::PermissionDenied(resolved_path));
+        }
         file.write(offset, data);
+        drop(file);
+        self.notify(&resolved_path, if created { ChangeKind::Create } else { ChangeKind::Modify });
         Ok(())
     }
 
@@ -132,23 +293,15 @@ impl FileSystem {
     ///
     /// If the offset is beyond the file size, returns an empty vector.
     /// If the requested length extends beyond the file, returns all the data till the end of the file.
-    ///
-    /// # Arguments
-    ///
-    /// * `path` - The path to the file
-    /// * `offset` - The byte offset where to start reading
-    /// * `len` - The number of bytes to read
-    ///
-    /// # Returns
-    ///
-    /// A vector containing the read data, which may be shorter than `len`
-    /// if the file is smaller than requested.
+    /// If `path` names a symlink, it is transparently resolved first.
     ///
     /// # Errors
     ///
     /// Returns [`FileSystemError::FileNotFound`] if the file doesn't exist.
     /// Returns [`FileSystemError::InvalidPath`] if the path is empty.
+    /// Returns [`FileSystemError::NotADirectory`] if the path names a directory, or a component along the way is not a directory.
     /// Returns [`FileSystemError::ReadError`] if the operation would cause overflow.
+    /// Returns [`FileSystemError::TooManyLinks`] if resolving a symlink chain exceeds the depth limit.
     ///
     /// # Examples
     ///
@@ -165,11 +318,8 @@ impl FileSystem {
     /// # });
     /// ```
     pub async fn read(&self, path: &str, offset: usize, len: usize) -> Result<Vec<u8>> {
-        if path.is_empty() {
-            return Err(FileSystemError::InvalidPath("Path cannot be empty".to_string()));
-        }
-
         if len == 0 {
+            split_path(path)?;
             return Ok(Vec::new());
         }
 
@@ -179,110 +329,819 @@ impl FileSystem {
             ));
         }
 
-        let file_rwlock = {
-            let files = self.files.read().await;
-            files.get(path).cloned()
-        };
-
-        match file_rwlock {
-            Some(file_rwlock) => {
-                let file = file_rwlock.read().await;
-                Ok(file.read(offset, len))
-            }
-            None => Err(FileSystemError::FileNotFound(path.to_string())),
-        }
+        let file_rwlock = self.resolve_file(path).await?;
+        let file = file_rwlock.read().await;
+        Ok(file.read(offset, len))
     }
-}
 
-impl Default for FileSystem {
-    fn default() -> Self { Self::new() }
-}
+    /// Opens a file, returning a [`FileHandle`] that implements
+    /// [`tokio::io::AsyncRead`], [`tokio::io::AsyncWrite`] and
+    /// [`tokio::io::AsyncSeek`], just like [`tokio::fs::File`]. If `path`
+    /// names a symlink, it is transparently resolved first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FileSystemError::FileNotFound`] if the file doesn't exist and `options` doesn't allow creation.
+    /// Returns [`FileSystemError::NotADirectory`] if a component of the path is not a directory.
+    /// Returns [`FileSystemError::AlreadyExists`] if a directory already exists at the path.
+    /// Returns [`FileSystemError::PermissionDenied`] if opened for writing and the file is read-only.
+    /// Returns [`FileSystemError::TooManyLinks`] if resolving a symlink chain exceeds the depth limit.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// use memfs::OpenOptions;
+    /// use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    ///
+    /// let fs = memfs::FileSystem::new();
+    /// let mut handle = fs.open("/file.txt", OpenOptions::new().write(true).create(true)).await.unwrap();
+    /// handle.write_all(b"hello").await.unwrap();
+    ///
+    /// let mut handle = fs.open("/file.txt", OpenOptions::new().read(true)).await.unwrap();
+    /// let mut content = String::new();
+    /// handle.read_to_string(&mut content).await.unwrap();
+    /// assert_eq!(content, "hello");
+    /// # });
+    /// ```
+    pub async fn open(&self, path: &str, options: OpenOptions) -> Result<FileHandle> {
+        let file = if options.should_create() {
+            let mut root = self.root.write().await;
+            let resolved_path = follow_symlinks(&root, path)?;
+            let components = split_path(&resolved_path)?;
+            let (name, parent_components) = components
+                .split_last()
+                .ok_or_else(|| FileSystemError::AlreadyExists(resolved_path.clone()))?;
+            let parent = navigate_mut(&mut root, parent_components, &resolved_path)?;
+
+            match parent.children.get(*name) {
+                Some(Node::Dir(_)) => return Err(FileSystemError::AlreadyExists(resolved_path)),
+                Some(Node::File(file)) => file.clone(),
+                Some(Node::Symlink(_)) => unreachable!("follow_symlinks already resolved any trailing symlink"),
+                None => {
+                    let file = Arc::new(RwLock::new(File::new()));
+                    parent.children.insert(name.to_string(), Node::File(file.clone()));
+                    file
+                }
+            }
+        } else {
+            self.resolve_file(path).await?
+        };
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        if (options.wants_write() || options.should_truncate()) && file.read().await.permissions().readonly() {
+            return Err(FileSystemError::PermissionDenied(path.to_string()));
+        }
 
-    #[tokio::test]
-    async fn test_basic_operations() -> Result<()> {
-        let fs = FileSystem::new();
+        if options.should_truncate() {
+            file.write().await.truncate();
+        }
 
-        fs.touch("/log.txt").await?;
+        Ok(FileHandle::new(file, &options).await)
+    }
 
-        fs.write("/log.txt", 0, b"hello").await?;
-        fs.write("/log.txt", 5, b" world").await?;
+    /// Returns a snapshot of a file's metadata.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FileSystemError::FileNotFound`] if the file doesn't exist.
+    /// Returns [`FileSystemError::NotADirectory`] if the path names a directory, or a component along the way is not a directory.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// let fs = memfs::FileSystem::new();
+    /// fs.write("/file.txt", 0, b"hello").await.unwrap();
+    ///
+    /// let metadata = fs.metadata("/file.txt").await.unwrap();
+    /// assert_eq!(metadata.len, 5);
+    /// # });
+    /// ```
+    pub async fn metadata(&self, path: &str) -> Result<Metadata> {
+        let file_rwlock = self.resolve_file(path).await?;
+        let file = file_rwlock.read().await;
+        Ok(file.metadata())
+    }
 
-        let content = fs.read("/log.txt", 0, 11).await?;
-        assert_eq!(content, b"hello world");
+    /// Returns the size of a file, in bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FileSystemError::FileNotFound`] if the file doesn't exist.
+    /// Returns [`FileSystemError::NotADirectory`] if the path names a directory, or a component along the way is not a directory.
+    pub async fn len(&self, path: &str) -> Result<u64> { Ok(self.metadata(path).await?.len) }
 
+    /// Sets a file's permissions.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FileSystemError::FileNotFound`] if the file doesn't exist.
+    /// Returns [`FileSystemError::NotADirectory`] if the path names a directory, or a component along the way is not a directory.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// use memfs::Permissions;
+    /// use memfs::error::FileSystemError;
+    ///
+    /// let fs = memfs::FileSystem::new();
+    /// fs.touch("/file.txt").await.unwrap();
+    ///
+    /// let mut permissions = Permissions::default();
+    /// permissions.set_readonly(true);
+    /// fs.set_permissions("/file.txt", permissions).await.unwrap();
+    ///
+    /// let result = fs.write("/file.txt", 0, b"nope").await;
+    /// assert!(matches!(result, Err(FileSystemError::PermissionDenied(_))));
+    /// # });
+    /// ```
+    pub async fn set_permissions(&self, path: &str, permissions: Permissions) -> Result<()> {
+        let file_rwlock = self.resolve_file(path).await?;
+        let mut file = file_rwlock.write().await;
+        file.set_permissions(permissions);
         Ok(())
     }
 
-    #[tokio::test]
-    async fn test_read_beyond_file() -> Result<()> {
-        let fs = FileSystem::new();
-
-        fs.touch("/test.txt").await?;
-        fs.write("/test.txt", 0, b"hello").await?;
+    /// Creates a directory at the specified path.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FileSystemError::NotADirectory`] if a component of the path is not a directory.
+    /// Returns [`FileSystemError::AlreadyExists`] if an entry already exists at the path.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// let fs = memfs::FileSystem::new();
+    /// fs.create_dir("/docs").await.unwrap();
+    /// # });
+    /// ```
+    pub async fn create_dir(&self, path: &str) -> Result<()> {
+        let components = split_path(path)?;
+        let (name, parent_components) = components
+            .split_last()
+            .ok_or_else(|| FileSystemError::AlreadyExists(path.to_string()))?;
 
-        let content = fs.read("/test.txt", 3, 10).await?;
-        assert_eq!(content, b"lo");
+        let mut root = self.root.write().await;
+        let parent = navigate_mut(&mut root, parent_components, path)?;
 
-        let content = fs.read("/test.txt", 10, 5).await?;
-        assert_eq!(content, b"");
+        if parent.children.contains_key(*name) {
+            return Err(FileSystemError::AlreadyExists(path.to_string()));
+        }
 
+        parent.children.insert(name.to_string(), Node::Dir(Dir::new()));
         Ok(())
     }
 
-    #[tokio::test]
-    async fn test_write_with_gap() -> Result<()> {
-        let fs = FileSystem::new();
-
-        fs.touch("/gap.txt").await?;
-
-        fs.write("/gap.txt", 5, b"world").await?;
+    /// Creates a directory and all of its missing parent directories.
+    ///
+    /// Does nothing if a directory already exists at the path.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FileSystemError::NotADirectory`] if a component of the path is a file.
+    /// Returns [`FileSystemError::AlreadyExists`] if a file already exists at the path.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// let fs = memfs::FileSystem::new();
+    /// fs.create_dir_all("/a/b/c").await.unwrap();
+    /// # });
+    /// ```
+    pub async fn create_dir_all(&self, path: &str) -> Result<()> {
+        let components = split_path(path)?;
+        let Some((last, parents)) = components.split_last() else {
+            return Ok(());
+        };
 
-        let content = fs.read("/gap.txt", 0, 10).await?;
-        assert_eq!(content, b"\0\0\0\0\0world");
+        let mut root = self.root.write().await;
+        let mut dir = &mut *root;
+        for name in parents {
+            let entry = dir.children.entry(name.to_string()).or_insert_with(|| Node::Dir(Dir::new()));
+            match entry {
+                Node::Dir(child) => dir = child,
+                Node::File(_) | Node::Symlink(_) => return Err(FileSystemError::NotADirectory(path.to_string())),
+            }
+        }
 
-        Ok(())
+        let entry = dir.children.entry(last.to_string()).or_insert_with(|| Node::Dir(Dir::new()));
+        match entry {
+            Node::Dir(_) => Ok(()),
+            Node::File(_) | Node::Symlink(_) => Err(FileSystemError::AlreadyExists(path.to_string())),
+        }
     }
 
-    #[tokio::test]
-    async fn test_write_with_gap_2() -> Result<()> {
-        let fs = FileSystem::new();
+    /// Lists the entries of a directory, sorted by name.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FileSystemError::FileNotFound`] if the directory doesn't exist.
+    /// Returns [`FileSystemError::NotADirectory`] if the path names a file.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// let fs = memfs::FileSystem::new();
+    /// fs.touch("/a.txt").await.unwrap();
+    /// fs.create_dir("/b").await.unwrap();
+    ///
+    /// let entries = fs.read_dir("/").await.unwrap();
+    /// assert_eq!(entries[0].name, "a.txt");
+    /// assert_eq!(entries[1].name, "b");
+    /// # });
+    /// ```
+    pub async fn read_dir(&self, path: &str) -> Result<Vec<DirEntry>> {
+        let components = split_path(path)?;
+
+        let root = self.root.read().await;
+        let dir = navigate(&root, &components, path)?;
+
+        let mut entries: Vec<DirEntry> = dir
+            .children
+            .iter()
+            .map(|(name, node)| DirEntry {
+                name: name.clone(),
+                is_dir: matches!(node, Node::Dir(_)),
+            })
+            .collect();
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(entries)
+    }
 
-        fs.touch("/gap2.txt").await?;
+    /// Removes an empty directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FileSystemError::FileNotFound`] if nothing exists at the path.
+    /// Returns [`FileSystemError::NotADirectory`] if the path names a file.
+    /// Returns [`FileSystemError::WriteError`] if the directory is not empty.
+    pub async fn remove_dir(&self, path: &str) -> Result<()> {
+        let components = split_path(path)?;
+        let (name, parent_components) = components
+            .split_last()
+            .ok_or_else(|| FileSystemError::InvalidPath("Path cannot be the root directory".to_string()))?;
+
+        let mut root = self.root.write().await;
+        let parent = navigate_mut(&mut root, parent_components, path)?;
+
+        match parent.children.get(*name) {
+            Some(Node::Dir(dir)) if dir.children.is_empty() => {
+                parent.children.remove(*name);
+                drop(root);
+                self.notify(path, ChangeKind::Delete);
+                Ok(())
+            }
+            Some(Node::Dir(_)) => Err(FileSystemError::WriteError(format!("Directory not empty: {path}"))),
+            Some(Node::File(_) | Node::Symlink(_)) => Err(FileSystemError::NotADirectory(path.to_string())),
+            None => Err(FileSystemError::FileNotFound(path.to_string())),
+        }
+    }
 
-        fs.write("/gap2.txt", 0, b"hello").await?;
-        fs.write("/gap2.txt", 10, b"world").await?;
+    /// Removes a directory and everything under it.
+    ///
+    /// Removes the symlink itself (not its target) if the path names one.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FileSystemError::FileNotFound`] if nothing exists at the path.
+    /// Returns [`FileSystemError::NotADirectory`] if the path names a file.
+    pub async fn remove_dir_all(&self, path: &str) -> Result<()> {
+        let components = split_path(path)?;
+        let (name, parent_components) = components
+            .split_last()
+            .ok_or_else(|| FileSystemError::InvalidPath("Path cannot be the root directory".to_string()))?;
+
+        let mut root = self.root.write().await;
+        let parent = navigate_mut(&mut root, parent_components, path)?;
+
+        match parent.children.get(*name) {
+            Some(Node::Dir(_) | Node::Symlink(_)) => {
+                parent.children.remove(*name);
+                drop(root);
+                self.notify(path, ChangeKind::Delete);
+                Ok(())
+            }
+            Some(Node::File(_)) => Err(FileSystemError::NotADirectory(path.to_string())),
+            None => Err(FileSystemError::FileNotFound(path.to_string())),
+        }
+    }
 
-        let content = fs.read("/gap2.txt", 0, 15).await?;
-        assert_eq!(content, b"hello\0\0\0\0\0world");
+    /// Removes a file.
+    ///
+    /// Removes the symlink itself (not its target) if the path names one.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FileSystemError::FileNotFound`] if nothing exists at the path.
+    /// Returns [`FileSystemError::InvalidPath`] if the path names a directory.
+    pub async fn remove_file(&self, path: &str) -> Result<()> {
+        let components = split_path(path)?;
+        let (name, parent_components) = components
+            .split_last()
+            .ok_or_else(|| FileSystemError::InvalidPath("Path cannot be the root directory".to_string()))?;
+
+        let mut root = self.root.write().await;
+        let parent = navigate_mut(&mut root, parent_components, path)?;
+
+        match parent.children.get(*name) {
+            Some(Node::File(_) | Node::Symlink(_)) => {
+                parent.children.remove(*name);
+                drop(root);
+                self.notify(path, ChangeKind::Delete);
+                Ok(())
+            }
+            Some(Node::Dir(_)) => Err(FileSystemError::InvalidPath(format!("{path} is a directory"))),
+            None => Err(FileSystemError::FileNotFound(path.to_string())),
+        }
+    }
 
+    /// Moves a file or directory from `src` to `dst`, overwriting any entry
+    /// already at `dst`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FileSystemError::FileNotFound`] if nothing exists at `src`.
+    /// Returns [`FileSystemError::NotADirectory`] if a component of either path is a file.
+    pub async fn rename(&self, src: &str, dst: &str) -> Result<()> {
+        let src_components = split_path(src)?;
+        let (src_name, src_parent_components) = src_components
+            .split_last()
+            .ok_or_else(|| FileSystemError::InvalidPath("Path cannot be the root directory".to_string()))?;
+
+        let dst_components = split_path(dst)?;
+        let (dst_name, dst_parent_components) = dst_components
+            .split_last()
+            .ok_or_else(|| FileSystemError::InvalidPath("Path cannot be the root directory".to_string()))?;
+
+        let mut root = self.root.write().await;
+
+        let src_parent = navigate_mut(&mut root, src_parent_components, src)?;
+        let node = src_parent
+            .children
+            .remove(*src_name)
+            .ok_or_else(|| FileSystemError::FileNotFound(src.to_string()))?;
+
+        let dst_parent = match navigate_mut(&mut root, dst_parent_components, dst) {
+            Ok(dir) => dir,
+            Err(err) => {
+                // Put the removed node back before surfacing the error.
+                let src_parent = navigate_mut(&mut root, src_parent_components, src)
+                    .expect("src_parent_components were just validated above");
+                src_parent.children.insert(src_name.to_string(), node);
+                return Err(err);
+            }
+        };
+        dst_parent.children.insert(dst_name.to_string(), node);
+        drop(root);
+        self.notify(dst, ChangeKind::Rename);
         Ok(())
     }
 
-    #[tokio::test]
-    async fn test_override() -> Result<()> {
-        let fs = FileSystem::new();
-
-        fs.touch("/override.txt").await?;
+    /// Creates a hard link at `dst` pointing to the same backing file as `src`.
+    ///
+    /// Since files are stored behind an `Arc<RwLock<File>>`, this simply
+    /// inserts that same `Arc` under `dst`: writes through either path are
+    /// visible through the other, mirroring [`tokio::fs::hard_link`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FileSystemError::FileNotFound`] if `src` doesn't exist.
+    /// Returns [`FileSystemError::AlreadyExists`] if an entry already exists at `dst`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// let fs = memfs::FileSystem::new();
+    /// fs.write("/a.txt", 0, b"hello").await.unwrap();
+    /// fs.hard_link("/a.txt", "/b.txt").await.unwrap();
+    ///
+    /// fs.write("/b.txt", 5, b" world").await.unwrap();
+    /// assert_eq!(fs.read("/a.txt", 0, 11).await.unwrap(), b"hello world");
+    /// # });
+    /// ```
+    pub async fn hard_link(&self, src: &str, dst: &str) -> Result<()> {
+        let file = self.resolve_file(src).await?;
 
-        fs.write("/override.txt", 0, b"hello dlrow").await?;
+        let dst_components = split_path(dst)?;
+        let (dst_name, dst_parent_components) = dst_components
+            .split_last()
+            .ok_or_else(|| FileSystemError::InvalidPath("Path cannot be the root directory".to_string()))?;
 
-        fs.write("/override.txt", 6, b"world").await?;
+        let mut root = self.root.write().await;
+        let parent = navigate_mut(&mut root, dst_parent_components, dst)?;
 
-        let content = fs.read("/override.txt", 0, 11).await?;
-        assert_eq!(content, b"hello world");
+        if parent.children.contains_key(*dst_name) {
+            return Err(FileSystemError::AlreadyExists(dst.to_string()));
+        }
 
+        parent.children.insert(dst_name.to_string(), Node::File(file));
+        drop(root);
+        self.notify(dst, ChangeKind::Create);
         Ok(())
     }
 
-    #[tokio::test]
-    async fn test_nonexistent_file() -> Result<()> {
-        let fs = FileSystem::new();
-
-        let content = fs.read("/nonexistent.txt", 0, 10).await;
+    /// Creates a symbolic link at `dst` pointing to `src`.
+    ///
+    /// `src` is stored as-is and doesn't need to exist yet; it is resolved
+    /// lazily whenever `dst` is looked up through [`FileSystem::read`],
+    /// [`FileSystem::write`] or [`FileSystem::open`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FileSystemError::AlreadyExists`] if an entry already exists at `dst`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// let fs = memfs::FileSystem::new();
+    /// fs.write("/a.txt", 0, b"hello").await.unwrap();
+    /// fs.symlink("/a.txt", "/link.txt").await.unwrap();
+    ///
+    /// assert_eq!(fs.read("/link.txt", 0, 5).await.unwrap(), b"hello");
+    /// assert_eq!(fs.read_link("/link.txt").await.unwrap(), "/a.txt");
+    /// # });
+    /// ```
+    pub async fn symlink(&self, src: &str, dst: &str) -> Result<()> {
+        split_path(src)?;
+
+        let dst_components = split_path(dst)?;
+        let (dst_name, dst_parent_components) = dst_components
+            .split_last()
+            .ok_or_else(|| FileSystemError::InvalidPath("Path cannot be the root directory".to_string()))?;
+
+        let mut root = self.root.write().await;
+        let parent = navigate_mut(&mut root, dst_parent_components, dst)?;
+
+        if parent.children.contains_key(*dst_name) {
+            return Err(FileSystemError::AlreadyExists(dst.to_string()));
+        }
+
+        parent.children.insert(dst_name.to_string(), Node::Symlink(src.to_string()));
+        drop(root);
+        self.notify(dst, ChangeKind::Create);
+        Ok(())
+    }
+
+    /// Returns the target path stored in the symbolic link at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FileSystemError::FileNotFound`] if nothing exists at the path.
+    /// Returns [`FileSystemError::InvalidPath`] if the path doesn't name a symlink.
+    pub async fn read_link(&self, path: &str) -> Result<String> {
+        let components = split_path(path)?;
+        let (name, parent_components) = components
+            .split_last()
+            .ok_or_else(|| FileSystemError::InvalidPath("Path cannot be the root directory".to_string()))?;
+
+        let root = self.root.read().await;
+        let parent = navigate(&root, parent_components, path)?;
+
+        match parent.children.get(*name) {
+            Some(Node::Symlink(target)) => Ok(target.clone()),
+            Some(_) => Err(FileSystemError::InvalidPath(format!("{path} is not a symlink"))),
+            None => Err(FileSystemError::FileNotFound(path.to_string())),
+        }
+    }
+
+    /// Resolves `path` to its backing file, following intermediate directories.
+    async fn resolve_file(&self, path: &str) -> Result<Arc<RwLock<File>>> { resolve_file_in(self.root.clone(), path).await }
+
+    /// Streams a file's contents as fixed-size chunks, without materializing
+    /// the whole file in memory.
+    ///
+    /// Errors (e.g. [`FileSystemError::FileNotFound`], or
+    /// [`FileSystemError::ReadError`] if `chunk_size` is zero) surface as the
+    /// first and only item yielded by the stream.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// use futures_util::StreamExt;
+    ///
+    /// let fs = memfs::FileSystem::new();
+    /// fs.write("/file.txt", 0, b"hello world").await.unwrap();
+    ///
+    /// let chunks: Vec<_> = fs.read_stream("/file.txt", 4).collect().await;
+    /// assert_eq!(chunks.len(), 3);
+    /// assert_eq!(chunks[0].as_ref().unwrap(), b"hell");
+    /// # });
+    /// ```
+    pub fn read_stream(&self, path: &str, chunk_size: usize) -> impl Stream<Item = Result<Vec<u8>>> {
+        FileStream::new(self.root.clone(), path.to_string(), chunk_size)
+    }
+
+    /// Searches the file system for matches of `query`, either in file
+    /// contents or in paths.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FileSystemError::FileNotFound`] if nothing exists at the query's path prefix.
+    /// Returns [`FileSystemError::ReadError`] if the query's pattern is not a valid regex.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// use memfs::{Pattern, SearchQuery};
+    ///
+    /// let fs = memfs::FileSystem::new();
+    /// fs.write("/notes.txt", 0, b"line one\ntodo: fix this\nline three").await.unwrap();
+    ///
+    /// let query = SearchQuery::new("/", Pattern::Literal("todo".to_string()));
+    /// let matches = fs.search(&query).await.unwrap();
+    ///
+    /// assert_eq!(matches.len(), 1);
+    /// assert_eq!(matches[0].line_number, 2);
+    /// # });
+    /// ```
+    pub async fn search(&self, query: &SearchQuery) -> Result<Vec<SearchMatch>> {
+        let pattern = match &query.pattern {
+            Pattern::Literal(text) => regex::escape(text),
+            Pattern::Regex(expr) => expr.clone(),
+        };
+        let matcher = RegexBuilder::new(&pattern)
+            .case_insensitive(!query.case_sensitive)
+            .build()
+            .map_err(|err| FileSystemError::ReadError(format!("invalid search pattern: {err}")))?;
+
+        let entries = {
+            let root = self.root.read().await;
+            let prefix = follow_symlinks(&root, &query.path_prefix)?;
+            let components = split_path(&prefix)?;
+            let mut entries = Vec::new();
+            match navigate(&root, &components, &prefix) {
+                Ok(dir) => collect_files(dir, &prefix, &mut entries),
+                Err(_) => {
+                    let (name, parent_components) =
+                        components.split_last().ok_or_else(|| FileSystemError::FileNotFound(prefix.clone()))?;
+                    let parent = navigate(&root, parent_components, &prefix)?;
+                    match parent.children.get(*name) {
+                        Some(Node::File(file)) => entries.push((prefix.clone(), file.clone())),
+                        Some(Node::Dir(_)) => unreachable!("navigate would have succeeded above"),
+                        Some(Node::Symlink(_)) => {
+                            unreachable!("follow_symlinks already resolved any trailing symlink")
+                        }
+                        None => return Err(FileSystemError::FileNotFound(prefix.clone())),
+                    }
+                }
+            }
+            entries
+        };
+
+        let mut matches = Vec::new();
+        for (path, file) in entries {
+            if let Some(max_results) = query.max_results {
+                if matches.len() >= max_results {
+                    break;
+                }
+            }
+
+            match query.mode {
+                SearchMode::Path => {
+                    if matcher.is_match(&path) {
+                        matches.push(SearchMatch { path: path.clone(), line_number: 0, byte_offset: 0, line: path });
+                    }
+                }
+                SearchMode::Contents => {
+                    let file = file.read().await;
+                    let mut byte_offset = 0;
+                    for (line_number, raw_line) in file.as_slice().split(|&byte| byte == b'\n').enumerate() {
+                        let line = String::from_utf8_lossy(raw_line).into_owned();
+                        if matcher.is_match(&line) {
+                            matches.push(SearchMatch { path: path.clone(), line_number: line_number + 1, byte_offset, line });
+
+                            if let Some(max_results) = query.max_results {
+                                if matches.len() >= max_results {
+                                    break;
+                                }
+                            }
+                        }
+                        byte_offset += raw_line.len() + 1;
+                    }
+                }
+            }
+        }
+
+        Ok(matches)
+    }
+
+    /// Serializes every file's path, contents and `readonly` permission into
+    /// a compact binary snapshot.
+    ///
+    /// The format is a short header followed by one record per file: a
+    /// length-prefixed path, a length-prefixed body, a permission flags
+    /// byte, and a trailing CRC32 checksum. Directories are implicit in the
+    /// recorded paths, so empty directories are not preserved. Hard links
+    /// are not preserved either: restoring a snapshot that had two paths
+    /// sharing one backing file yields two independent files.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// use memfs::RestoreMode;
+    ///
+    /// let fs = memfs::FileSystem::new();
+    /// fs.write("/file.txt", 0, b"hello").await.unwrap();
+    ///
+    /// let bytes = fs.snapshot().await;
+    /// let restored = memfs::FileSystem::restore(&bytes, RestoreMode::Abort).await.unwrap();
+    /// assert_eq!(restored.read("/file.txt", 0, 5).await.unwrap(), b"hello");
+    /// # });
+    /// ```
+    pub async fn snapshot(&self) -> Vec<u8> {
+        let root = self.root.read().await;
+        let mut entries = Vec::new();
+        collect_files(&root, "", &mut entries);
+        drop(root);
+
+        let mut buf = Vec::new();
+        snapshot::write_header(&mut buf);
+
+        for (path, file) in entries {
+            let file = file.read().await;
+            let flags = if file.permissions().readonly() { snapshot::FLAG_READONLY } else { 0 };
+            snapshot::write_record(&mut buf, &path, file.as_slice(), flags);
+        }
+
+        buf
+    }
+
+    /// Rebuilds a [`FileSystem`] from a snapshot produced by [`FileSystem::snapshot`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FileSystemError::Corrupted`] if the header is missing or
+    /// unrecognized, if a record is truncated, or if a record's checksum
+    /// doesn't match and `mode` is [`RestoreMode::Abort`].
+    pub async fn restore(bytes: &[u8], mode: RestoreMode) -> Result<FileSystem> {
+        let mut offset = snapshot::read_header(bytes)?;
+        let fs = FileSystem::new();
+
+        while offset < bytes.len() {
+            let record = snapshot::read_record(bytes, &mut offset)?;
+
+            if !record.checksum_ok {
+                match mode {
+                    RestoreMode::Abort => return Err(FileSystemError::Corrupted(record.path)),
+                    RestoreMode::Skip => continue,
+                }
+            }
+
+            if let Some((parent, _)) = record.path.rsplit_once('/') {
+                if !parent.is_empty() {
+                    fs.create_dir_all(parent).await?;
+                }
+            }
+            fs.write(&record.path, 0, &record.data).await?;
+
+            if record.flags & snapshot::FLAG_READONLY != 0 {
+                let mut permissions = Permissions::default();
+                permissions.set_readonly(true);
+                fs.set_permissions(&record.path, permissions).await?;
+            }
+        }
+
+        Ok(fs)
+    }
+}
+
+/// Recursively collects every file under `dir`, paired with its full path.
+///
+/// Symlinks are skipped: they aren't indexed by [`FileSystem::search`] or
+/// included in [`FileSystem::snapshot`].
+fn collect_files(dir: &Dir, prefix: &str, out: &mut Vec<(String, Arc<RwLock<File>>)>) {
+    let mut names: Vec<&String> = dir.children.keys().collect();
+    names.sort();
+
+    for name in names {
+        let path = format!("{}/{name}", prefix.trim_end_matches('/'));
+        match &dir.children[name] {
+            Node::File(file) => out.push((path, file.clone())),
+            Node::Dir(child) => collect_files(child, &path, out),
+            Node::Symlink(_) => {}
+        }
+    }
+}
+
+/// Resolves `path` to its backing file within `root`, following intermediate directories.
+async fn resolve_file_in(root: Arc<RwLock<Dir>>, path: &str) -> Result<Arc<RwLock<File>>> {
+    let root = root.read().await;
+    let resolved_path = follow_symlinks(&root, path)?;
+
+    let components = split_path(&resolved_path)?;
+    let (name, parent_components) = components
+        .split_last()
+        .ok_or_else(|| FileSystemError::NotADirectory(resolved_path.clone()))?;
+
+    let parent = navigate(&root, parent_components, &resolved_path)?;
+
+    match parent.children.get(*name) {
+        Some(Node::File(file)) => Ok(file.clone()),
+        Some(Node::Dir(_)) => Err(FileSystemError::NotADirectory(resolved_path)),
+        Some(Node::Symlink(_)) => unreachable!("follow_symlinks already resolved any trailing symlink"),
+        None => Err(FileSystemError::FileNotFound(resolved_path)),
+    }
+}
+
+impl Default for FileSystem {
+    fn default() -> Self { Self::new() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_basic_operations() -> Result<()> {
+        let fs = FileSystem::new();
+
+        fs.touch("/log.txt").await?;
+
+        fs.write("/log.txt", 0, b"hello").await?;
+        fs.write("/log.txt", 5, b" world").await?;
+
+        let content = fs.read("/log.txt", 0, 11).await?;
+        assert_eq!(content, b"hello world");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_beyond_file() -> Result<()> {
+        let fs = FileSystem::new();
+
+        fs.touch("/test.txt").await?;
+        fs.write("/test.txt", 0, b"hello").await?;
+
+        let content = fs.read("/test.txt", 3, 10).await?;
+        assert_eq!(content, b"lo");
+
+        let content = fs.read("/test.txt", 10, 5).await?;
+        assert_eq!(content, b"");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_with_gap() -> Result<()> {
+        let fs = FileSystem::new();
+
+        fs.touch("/gap.txt").await?;
+
+        fs.write("/gap.txt", 5, b"world").await?;
+
+        let content = fs.read("/gap.txt", 0, 10).await?;
+        assert_eq!(content, b"\0\0\0\0\0world");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_with_gap_2() -> Result<()> {
+        let fs = FileSystem::new();
+
+        fs.touch("/gap2.txt").await?;
+
+        fs.write("/gap2.txt", 0, b"hello").await?;
+        fs.write("/gap2.txt", 10, b"world").await?;
+
+        let content = fs.read("/gap2.txt", 0, 15).await?;
+        assert_eq!(content, b"hello\0\0\0\0\0world");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_override() -> Result<()> {
+        let fs = FileSystem::new();
+
+        fs.touch("/override.txt").await?;
+
+        fs.write("/override.txt", 0, b"hello dlrow").await?;
+
+        fs.write("/override.txt", 6, b"world").await?;
+
+        let content = fs.read("/override.txt", 0, 11).await?;
+        assert_eq!(content, b"hello world");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_nonexistent_file() -> Result<()> {
+        let fs = FileSystem::new();
+
+        let content = fs.read("/nonexistent.txt", 0, 10).await;
         assert!(matches!(content, Err(FileSystemError::FileNotFound(_))));
 
         Ok(())
@@ -491,4 +1350,659 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_create_dir_and_nested_file() -> Result<()> {
+        let fs = FileSystem::new();
+
+        fs.create_dir("/docs").await?;
+        fs.touch("/docs/readme.txt").await?;
+        fs.write("/docs/readme.txt", 0, b"hi").await?;
+
+        assert_eq!(fs.read("/docs/readme.txt", 0, 2).await?, b"hi");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_create_dir_missing_parent() -> Result<()> {
+        let fs = FileSystem::new();
+
+        let result = fs.create_dir("/a/b").await;
+        assert!(matches!(result, Err(FileSystemError::FileNotFound(_))));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_create_dir_already_exists() -> Result<()> {
+        let fs = FileSystem::new();
+
+        fs.create_dir("/docs").await?;
+        let result = fs.create_dir("/docs").await;
+        assert!(matches!(result, Err(FileSystemError::AlreadyExists(_))));
+
+        fs.touch("/file.txt").await?;
+        let result = fs.create_dir("/file.txt").await;
+        assert!(matches!(result, Err(FileSystemError::AlreadyExists(_))));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_create_dir_all() -> Result<()> {
+        let fs = FileSystem::new();
+
+        fs.create_dir_all("/a/b/c").await?;
+        fs.touch("/a/b/c/leaf.txt").await?;
+
+        assert_eq!(fs.read_dir("/a/b/c").await?, vec![DirEntry {
+            name: "leaf.txt".to_string(),
+            is_dir: false
+        }]);
+
+        // Calling it again on an existing tree is a no-op.
+        fs.create_dir_all("/a/b/c").await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_touch_and_create_dir_conflict() -> Result<()> {
+        let fs = FileSystem::new();
+
+        fs.touch("/item").await?;
+        let result = fs.create_dir_all("/item/nested").await;
+        assert!(matches!(result, Err(FileSystemError::NotADirectory(_))));
+
+        fs.create_dir("/dir").await?;
+        let result = fs.touch("/dir").await;
+        assert!(matches!(result, Err(FileSystemError::AlreadyExists(_))));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_create_dir_all_file_at_final_component() -> Result<()> {
+        let fs = FileSystem::new();
+
+        fs.touch("/item").await?;
+        let result = fs.create_dir_all("/item").await;
+        assert!(matches!(result, Err(FileSystemError::AlreadyExists(_))));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_dir_sorted() -> Result<()> {
+        let fs = FileSystem::new();
+
+        fs.touch("/b.txt").await?;
+        fs.touch("/a.txt").await?;
+        fs.create_dir("/c_dir").await?;
+
+        let entries = fs.read_dir("/").await?;
+        assert_eq!(
+            entries,
+            vec![
+                DirEntry { name: "a.txt".to_string(), is_dir: false },
+                DirEntry { name: "b.txt".to_string(), is_dir: false },
+                DirEntry { name: "c_dir".to_string(), is_dir: true },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_dir_on_file_fails() -> Result<()> {
+        let fs = FileSystem::new();
+
+        fs.touch("/file.txt").await?;
+        let result = fs.read_dir("/file.txt").await;
+        assert!(matches!(result, Err(FileSystemError::NotADirectory(_))));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_remove_file() -> Result<()> {
+        let fs = FileSystem::new();
+
+        fs.touch("/file.txt").await?;
+        fs.remove_file("/file.txt").await?;
+
+        let result = fs.read("/file.txt", 0, 1).await;
+        assert!(matches!(result, Err(FileSystemError::FileNotFound(_))));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_remove_dir_requires_empty() -> Result<()> {
+        let fs = FileSystem::new();
+
+        fs.create_dir("/docs").await?;
+        fs.touch("/docs/readme.txt").await?;
+
+        let result = fs.remove_dir("/docs").await;
+        assert!(matches!(result, Err(FileSystemError::WriteError(_))));
+
+        fs.remove_file("/docs/readme.txt").await?;
+        fs.remove_dir("/docs").await?;
+
+        let result = fs.read_dir("/docs").await;
+        assert!(matches!(result, Err(FileSystemError::FileNotFound(_))));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_remove_dir_all() -> Result<()> {
+        let fs = FileSystem::new();
+
+        fs.create_dir_all("/a/b").await?;
+        fs.touch("/a/b/leaf.txt").await?;
+
+        fs.remove_dir_all("/a").await?;
+
+        let result = fs.read_dir("/a").await;
+        assert!(matches!(result, Err(FileSystemError::FileNotFound(_))));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rename_file() -> Result<()> {
+        let fs = FileSystem::new();
+
+        fs.write("/src.txt", 0, b"payload").await?;
+        fs.create_dir("/dst_dir").await?;
+
+        fs.rename("/src.txt", "/dst_dir/dst.txt").await?;
+
+        assert_eq!(fs.read("/dst_dir/dst.txt", 0, 7).await?, b"payload");
+        let result = fs.read("/src.txt", 0, 1).await;
+        assert!(matches!(result, Err(FileSystemError::FileNotFound(_))));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rename_missing_source_leaves_tree_untouched() -> Result<()> {
+        let fs = FileSystem::new();
+
+        let result = fs.rename("/missing.txt", "/dst.txt").await;
+        assert!(matches!(result, Err(FileSystemError::FileNotFound(_))));
+
+        let result = fs.read_dir("/").await?;
+        assert!(result.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_open_write_then_read() -> Result<()> {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let fs = FileSystem::new();
+
+        let mut handle = fs.open("/file.txt", OpenOptions::new().write(true).create(true)).await?;
+        handle.write_all(b"hello world").await.unwrap();
+
+        let mut handle = fs.open("/file.txt", OpenOptions::new().read(true)).await?;
+        let mut content = String::new();
+        handle.read_to_string(&mut content).await.unwrap();
+        assert_eq!(content, "hello world");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_handle_write_denied_without_write_mode() -> Result<()> {
+        use tokio::io::AsyncWriteExt;
+
+        let fs = FileSystem::new();
+        fs.write("/file.txt", 0, b"hello").await?;
+
+        let mut handle = fs.open("/file.txt", OpenOptions::new().read(true)).await?;
+        let result = handle.write_all(b"nope").await;
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::PermissionDenied);
+
+        assert_eq!(fs.read("/file.txt", 0, 100).await?, b"hello");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_handle_read_denied_without_read_mode() -> Result<()> {
+        use tokio::io::AsyncReadExt;
+
+        let fs = FileSystem::new();
+        fs.write("/file.txt", 0, b"hello").await?;
+
+        let mut handle = fs.open("/file.txt", OpenOptions::new().write(true)).await?;
+        let mut buf = Vec::new();
+        let result = handle.read_to_end(&mut buf).await;
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::PermissionDenied);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_open_without_create_missing_file() -> Result<()> {
+        let fs = FileSystem::new();
+
+        let result = fs.open("/missing.txt", OpenOptions::new().read(true)).await;
+        assert!(matches!(result, Err(FileSystemError::FileNotFound(_))));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_open_truncate() -> Result<()> {
+        use tokio::io::AsyncWriteExt;
+
+        let fs = FileSystem::new();
+        fs.write("/file.txt", 0, b"old content").await?;
+
+        let mut handle = fs.open("/file.txt", OpenOptions::new().write(true).truncate(true)).await?;
+        handle.write_all(b"new").await.unwrap();
+
+        assert_eq!(fs.read("/file.txt", 0, 100).await?, b"new");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_open_append() -> Result<()> {
+        use tokio::io::AsyncWriteExt;
+
+        let fs = FileSystem::new();
+        fs.write("/log.txt", 0, b"first;").await?;
+
+        let mut handle = fs.open("/log.txt", OpenOptions::new().write(true).append(true)).await?;
+        handle.write_all(b"second;").await.unwrap();
+
+        assert_eq!(fs.read("/log.txt", 0, 100).await?, b"first;second;");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_handle_seek() -> Result<()> {
+        use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+        let fs = FileSystem::new();
+        fs.write("/file.txt", 0, b"0123456789").await?;
+
+        let mut handle = fs.open("/file.txt", OpenOptions::new().read(true)).await?;
+
+        handle.seek(std::io::SeekFrom::Start(5)).await.unwrap();
+        let mut buf = [0u8; 2];
+        handle.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"56");
+
+        handle.seek(std::io::SeekFrom::Current(-2)).await.unwrap();
+        handle.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"56");
+
+        handle.seek(std::io::SeekFrom::End(-1)).await.unwrap();
+        let mut buf = [0u8; 1];
+        handle.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"9");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_metadata_len_and_timestamps() -> Result<()> {
+        let fs = FileSystem::new();
+
+        fs.write("/file.txt", 0, b"hello").await?;
+        let metadata = fs.metadata("/file.txt").await?;
+        assert_eq!(metadata.len, 5);
+        assert!(metadata.modified >= metadata.created);
+        assert_eq!(fs.len("/file.txt").await?, 5);
+
+        fs.write("/file.txt", 5, b" world").await?;
+        let updated = fs.metadata("/file.txt").await?;
+        assert_eq!(updated.len, 11);
+        assert!(updated.modified >= metadata.modified);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_readonly_permission_denied() -> Result<()> {
+        let fs = FileSystem::new();
+
+        fs.touch("/file.txt").await?;
+
+        let mut permissions = Permissions::default();
+        permissions.set_readonly(true);
+        fs.set_permissions("/file.txt", permissions).await?;
+
+        assert!(fs.metadata("/file.txt").await?.permissions.readonly());
+
+        let result = fs.write("/file.txt", 0, b"nope").await;
+        assert!(matches!(result, Err(FileSystemError::PermissionDenied(_))));
+
+        let result = fs.open("/file.txt", OpenOptions::new().write(true)).await;
+        assert!(matches!(result, Err(FileSystemError::PermissionDenied(_))));
+
+        let result = fs.open("/file.txt", OpenOptions::new().truncate(true)).await;
+        assert!(matches!(result, Err(FileSystemError::PermissionDenied(_))));
+
+        let mut handle = fs.open("/file.txt", OpenOptions::new().read(true)).await?;
+        let mut buf = Vec::new();
+        tokio::io::AsyncReadExt::read_to_end(&mut handle, &mut buf).await.unwrap();
+        assert_eq!(buf, b"");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_stream_chunks() -> Result<()> {
+        use futures_util::StreamExt;
+
+        let fs = FileSystem::new();
+        fs.write("/file.txt", 0, b"hello world").await?;
+
+        let chunks: Vec<Vec<u8>> = fs
+            .read_stream("/file.txt", 4)
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+
+        assert_eq!(chunks, vec![b"hell".to_vec(), b"o wo".to_vec(), b"rld".to_vec()]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_stream_missing_file() -> Result<()> {
+        use futures_util::StreamExt;
+
+        let fs = FileSystem::new();
+
+        let mut stream = fs.read_stream("/missing.txt", 4);
+        let first = stream.next().await;
+        assert!(matches!(first, Some(Err(FileSystemError::FileNotFound(_)))));
+        assert!(stream.next().await.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_stream_empty_file() -> Result<()> {
+        use futures_util::StreamExt;
+
+        let fs = FileSystem::new();
+        fs.touch("/empty.txt").await?;
+
+        let chunks: Vec<_> = fs.read_stream("/empty.txt", 4).collect().await;
+        assert!(chunks.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_stream_rejects_zero_chunk_size() -> Result<()> {
+        use futures_util::StreamExt;
+
+        let fs = FileSystem::new();
+        fs.write("/file.txt", 0, b"hello world").await?;
+
+        let mut stream = fs.read_stream("/file.txt", 0);
+        let first = stream.next().await;
+        assert!(matches!(first, Some(Err(FileSystemError::ReadError(_)))));
+        assert!(stream.next().await.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_watch_create_and_modify() -> Result<()> {
+        use futures_util::StreamExt;
+
+        let fs = FileSystem::new();
+        let mut changes = fs.watch("/", ChangeKindSet::all());
+
+        fs.touch("/file.txt").await?;
+        let event = changes.next().await.unwrap();
+        assert_eq!(event, ChangeEvent { path: "/file.txt".to_string(), kind: ChangeKind::Create });
+
+        fs.write("/file.txt", 0, b"hi").await?;
+        let event = changes.next().await.unwrap();
+        assert_eq!(event, ChangeEvent { path: "/file.txt".to_string(), kind: ChangeKind::Modify });
+
+        fs.remove_file("/file.txt").await?;
+        let event = changes.next().await.unwrap();
+        assert_eq!(event, ChangeEvent { path: "/file.txt".to_string(), kind: ChangeKind::Delete });
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_watch_filters_by_prefix_and_kind() -> Result<()> {
+        use futures_util::StreamExt;
+        use tokio::time::{timeout, Duration};
+
+        let fs = FileSystem::new();
+        let mut changes = fs.watch("/watched/", ChangeKindSet::new().with(ChangeKind::Create));
+
+        fs.touch("/other.txt").await?;
+        fs.create_dir("/watched").await?;
+        fs.touch("/watched/file.txt").await?;
+        fs.write("/watched/file.txt", 0, b"hi").await?;
+
+        let event = changes.next().await.unwrap();
+        assert_eq!(event, ChangeEvent { path: "/watched/file.txt".to_string(), kind: ChangeKind::Create });
+
+        let modify_seen = timeout(Duration::from_millis(50), changes.next()).await;
+        assert!(modify_seen.is_err(), "the Modify event should have been filtered out");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_contents_literal() -> Result<()> {
+        let fs = FileSystem::new();
+        fs.write("/notes.txt", 0, b"line one\ntodo: fix this\nline three").await?;
+
+        let query = SearchQuery::new("/", Pattern::Literal("todo".to_string()));
+        let matches = fs.search(&query).await?;
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].path, "/notes.txt");
+        assert_eq!(matches[0].line_number, 2);
+        assert_eq!(matches[0].line, "todo: fix this");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_case_insensitive() -> Result<()> {
+        let fs = FileSystem::new();
+        fs.write("/a.txt", 0, b"Hello World").await?;
+
+        let sensitive = SearchQuery::new("/", Pattern::Literal("hello".to_string()));
+        assert!(fs.search(&sensitive).await?.is_empty());
+
+        let insensitive = SearchQuery::new("/", Pattern::Literal("hello".to_string())).case_sensitive(false);
+        assert_eq!(fs.search(&insensitive).await?.len(), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_max_results() -> Result<()> {
+        let fs = FileSystem::new();
+        fs.write("/a.txt", 0, b"match\nmatch\nmatch").await?;
+
+        let query = SearchQuery::new("/", Pattern::Regex("match".to_string())).max_results(2);
+        let matches = fs.search(&query).await?;
+
+        assert_eq!(matches.len(), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_path_mode() -> Result<()> {
+        let fs = FileSystem::new();
+        fs.create_dir("/src").await?;
+        fs.touch("/src/main.rs").await?;
+        fs.touch("/README.md").await?;
+
+        let query = SearchQuery::new("/", Pattern::Literal(".rs".to_string())).mode(SearchMode::Path);
+        let matches = fs.search(&query).await?;
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].path, "/src/main.rs");
+        assert_eq!(matches[0].line_number, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_restore_round_trip() -> Result<()> {
+        let fs = FileSystem::new();
+        fs.create_dir_all("/a/b").await?;
+        fs.write("/a/b/file.txt", 0, b"hello").await?;
+        fs.touch("/empty.txt").await?;
+
+        let bytes = fs.snapshot().await;
+        let restored = FileSystem::restore(&bytes, RestoreMode::Abort).await?;
+
+        assert_eq!(restored.read("/a/b/file.txt", 0, 5).await?, b"hello");
+        assert_eq!(restored.len("/empty.txt").await?, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_restore_preserves_readonly_permission() -> Result<()> {
+        let fs = FileSystem::new();
+        fs.write("/file.txt", 0, b"hello").await?;
+
+        let mut permissions = Permissions::default();
+        permissions.set_readonly(true);
+        fs.set_permissions("/file.txt", permissions).await?;
+
+        let bytes = fs.snapshot().await;
+        let restored = FileSystem::restore(&bytes, RestoreMode::Abort).await?;
+
+        assert!(restored.metadata("/file.txt").await?.permissions.readonly());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_restore_empty_snapshot() -> Result<()> {
+        let fs = FileSystem::new();
+        let bytes = fs.snapshot().await;
+
+        let restored = FileSystem::restore(&bytes, RestoreMode::Abort).await?;
+        assert!(restored.read_dir("/").await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_restore_rejects_bad_header() {
+        let result = FileSystem::restore(b"not a snapshot", RestoreMode::Abort).await;
+        assert!(matches!(result, Err(FileSystemError::Corrupted(_))));
+    }
+
+    #[tokio::test]
+    async fn test_restore_corrupted_record() -> Result<()> {
+        let fs = FileSystem::new();
+        fs.write("/file.txt", 0, b"hello").await?;
+
+        let mut bytes = fs.snapshot().await;
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xFF;
+
+        let aborted = FileSystem::restore(&bytes, RestoreMode::Abort).await;
+        assert!(matches!(aborted, Err(FileSystemError::Corrupted(_))));
+
+        let skipped = FileSystem::restore(&bytes, RestoreMode::Skip).await?;
+        assert!(matches!(skipped.read("/file.txt", 0, 5).await, Err(FileSystemError::FileNotFound(_))));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_hard_link_shares_backing_file() -> Result<()> {
+        let fs = FileSystem::new();
+        fs.write("/a.txt", 0, b"hello").await?;
+        fs.hard_link("/a.txt", "/b.txt").await?;
+
+        fs.write("/b.txt", 5, b" world").await?;
+        assert_eq!(fs.read("/a.txt", 0, 11).await?, b"hello world");
+
+        fs.remove_file("/a.txt").await?;
+        assert_eq!(fs.read("/b.txt", 0, 11).await?, b"hello world");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_hard_link_requires_existing_source() {
+        let fs = FileSystem::new();
+        let result = fs.hard_link("/missing.txt", "/b.txt").await;
+        assert!(matches!(result, Err(FileSystemError::FileNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_symlink_transparently_resolved() -> Result<()> {
+        let fs = FileSystem::new();
+        fs.write("/a.txt", 0, b"hello").await?;
+        fs.symlink("/a.txt", "/link.txt").await?;
+
+        assert_eq!(fs.read("/link.txt", 0, 5).await?, b"hello");
+        assert_eq!(fs.read_link("/link.txt").await?, "/a.txt");
+
+        fs.write("/link.txt", 5, b" world").await?;
+        assert_eq!(fs.read("/a.txt", 0, 11).await?, b"hello world");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_symlink_chain_is_resolved() -> Result<()> {
+        let fs = FileSystem::new();
+        fs.write("/a.txt", 0, b"hello").await?;
+        fs.symlink("/a.txt", "/b.txt").await?;
+        fs.symlink("/b.txt", "/c.txt").await?;
+
+        assert_eq!(fs.read("/c.txt", 0, 5).await?, b"hello");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_symlink_loop_detected() -> Result<()> {
+        let fs = FileSystem::new();
+        fs.symlink("/b.txt", "/a.txt").await?;
+        fs.symlink("/a.txt", "/b.txt").await?;
+
+        let result = fs.read("/a.txt", 0, 1).await;
+        assert!(matches!(result, Err(FileSystemError::TooManyLinks(_))));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_link_on_non_symlink_fails() -> Result<()> {
+        let fs = FileSystem::new();
+        fs.touch("/a.txt").await?;
+
+        let result = fs.read_link("/a.txt").await;
+        assert!(matches!(result, Err(FileSystemError::InvalidPath(_))));
+
+        Ok(())
+    }
 }
@@ -0,0 +1,28 @@
+use std::time::SystemTime;
+
+/// Permission bits tracked for a file, mirroring [`std::fs::Permissions`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Permissions {
+    readonly: bool,
+}
+
+impl Permissions {
+    /// Returns whether the file is marked read-only.
+    pub fn readonly(&self) -> bool { self.readonly }
+
+    /// Sets whether the file is marked read-only.
+    pub fn set_readonly(&mut self, readonly: bool) { self.readonly = readonly; }
+}
+
+/// Metadata about a file, mirroring [`std::fs::Metadata`].
+#[derive(Debug, Clone, Copy)]
+pub struct Metadata {
+    /// The size of the file, in bytes.
+    pub len: u64,
+    /// When the file was created.
+    pub created: SystemTime,
+    /// When the file was last modified.
+    pub modified: SystemTime,
+    /// The file's permissions.
+    pub permissions: Permissions,
+}
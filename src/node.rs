@@ -0,0 +1,24 @@
+use std::{collections::HashMap, sync::Arc};
+
+use tokio::sync::RwLock;
+
+use crate::file::File;
+
+/// A single entry in the directory tree: a subdirectory, a file, or a
+/// symbolic link to another path.
+#[derive(Debug)]
+pub(crate) enum Node {
+    Dir(Dir),
+    File(Arc<RwLock<File>>),
+    Symlink(String),
+}
+
+/// A directory node, holding its children by name.
+#[derive(Debug, Default)]
+pub(crate) struct Dir {
+    pub(crate) children: HashMap<String, Node>,
+}
+
+impl Dir {
+    pub(crate) fn new() -> Self { Self::default() }
+}
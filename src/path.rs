@@ -0,0 +1,15 @@
+use crate::error::FileSystemError;
+
+/// Splits a path into its normalized components.
+///
+/// Leading, trailing and repeated `/` separators are collapsed, so
+/// `"/a//b/"` and `"a/b"` both yield `["a", "b"]`. An empty path is
+/// rejected; the root path (`"/"` or `""` after trimming) yields an
+/// empty component list.
+pub(crate) fn split_path(path: &str) -> Result<Vec<&str>, FileSystemError> {
+    if path.is_empty() {
+        return Err(FileSystemError::InvalidPath("Path cannot be empty".to_string()));
+    }
+
+    Ok(path.split('/').filter(|c| !c.is_empty()).collect())
+}
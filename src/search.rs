@@ -0,0 +1,72 @@
+/// The text to look for in a [`SearchQuery`].
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    /// Matched literally, with no regex special characters.
+    Literal(String),
+    /// Matched as a regular expression.
+    Regex(String),
+}
+
+/// What part of an entry a [`SearchQuery`] matches against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchMode {
+    /// Scan each file's bytes, line by line.
+    Contents,
+    /// Match against the entry's path only.
+    Path,
+}
+
+/// A query passed to [`crate::FileSystem::search`].
+#[derive(Debug, Clone)]
+pub struct SearchQuery {
+    pub(crate) path_prefix: String,
+    pub(crate) pattern: Pattern,
+    pub(crate) mode: SearchMode,
+    pub(crate) case_sensitive: bool,
+    pub(crate) max_results: Option<usize>,
+}
+
+impl SearchQuery {
+    /// Creates a query that scans file contents under `path_prefix`, case-sensitively and
+    /// without a result cap.
+    pub fn new(path_prefix: impl Into<String>, pattern: Pattern) -> Self {
+        Self {
+            path_prefix: path_prefix.into(),
+            pattern,
+            mode: SearchMode::Contents,
+            case_sensitive: true,
+            max_results: None,
+        }
+    }
+
+    /// Sets whether to match file contents or paths.
+    pub fn mode(mut self, mode: SearchMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets whether the match is case-sensitive.
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// Caps the number of matches returned.
+    pub fn max_results(mut self, max_results: usize) -> Self {
+        self.max_results = Some(max_results);
+        self
+    }
+}
+
+/// A single match produced by [`crate::FileSystem::search`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchMatch {
+    /// The path of the matching entry.
+    pub path: String,
+    /// The 1-based line number of the match. `0` for [`SearchMode::Path`] matches.
+    pub line_number: usize,
+    /// The byte offset of the matching line within the file. `0` for [`SearchMode::Path`] matches.
+    pub byte_offset: usize,
+    /// The matching line's text (lossily decoded), or the path itself for [`SearchMode::Path`] matches.
+    pub line: String,
+}
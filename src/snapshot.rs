@@ -0,0 +1,93 @@
+use crc32fast::Hasher;
+
+use crate::{FileSystemError, Result};
+
+const MAGIC: &[u8; 4] = b"MFS1";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = MAGIC.len() + 1;
+
+/// Controls how [`crate::FileSystem::restore`] handles a record whose
+/// checksum doesn't match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestoreMode {
+    /// Stop and return [`FileSystemError::Corrupted`] on the first bad record.
+    Abort,
+    /// Drop the bad record and keep restoring the rest of the snapshot.
+    Skip,
+}
+
+/// Bit set in a record's flags byte when the file's `readonly` permission is set.
+pub(crate) const FLAG_READONLY: u8 = 0b0000_0001;
+
+/// One decoded snapshot record.
+pub(crate) struct Record {
+    pub(crate) path: String,
+    pub(crate) data: Vec<u8>,
+    pub(crate) flags: u8,
+    pub(crate) checksum_ok: bool,
+}
+
+/// Appends the snapshot header to `buf`.
+pub(crate) fn write_header(buf: &mut Vec<u8>) {
+    buf.extend_from_slice(MAGIC);
+    buf.push(VERSION);
+}
+
+/// Appends one file record (length-prefixed path, length-prefixed body,
+/// a permission flags byte, then a trailing checksum) to `buf`.
+pub(crate) fn write_record(buf: &mut Vec<u8>, path: &str, data: &[u8], flags: u8) {
+    let path_bytes = path.as_bytes();
+
+    let mut hasher = Hasher::new();
+    hasher.update(path_bytes);
+    hasher.update(data);
+    hasher.update(&[flags]);
+
+    buf.extend_from_slice(&(path_bytes.len() as u32).to_le_bytes());
+    buf.extend_from_slice(path_bytes);
+    buf.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    buf.extend_from_slice(data);
+    buf.push(flags);
+    buf.extend_from_slice(&hasher.finalize().to_le_bytes());
+}
+
+/// Validates the snapshot header, returning the offset of the first record.
+pub(crate) fn read_header(bytes: &[u8]) -> Result<usize> {
+    if bytes.len() < HEADER_LEN || bytes[..MAGIC.len()] != *MAGIC || bytes[MAGIC.len()] != VERSION {
+        return Err(FileSystemError::Corrupted("<header>".to_string()));
+    }
+    Ok(HEADER_LEN)
+}
+
+/// Reads one record starting at `*offset`, advancing it past the record.
+pub(crate) fn read_record(bytes: &[u8], offset: &mut usize) -> Result<Record> {
+    let path_len = read_u32(bytes, offset)? as usize;
+    let path_bytes = read_slice(bytes, offset, path_len)?;
+    let path = String::from_utf8(path_bytes.to_vec()).map_err(|_| FileSystemError::Corrupted("<path>".to_string()))?;
+
+    let data_len = read_u32(bytes, offset)? as usize;
+    let data = read_slice(bytes, offset, data_len)?.to_vec();
+
+    let flags = read_slice(bytes, offset, 1)?[0];
+
+    let checksum = read_u32(bytes, offset)?;
+
+    let mut hasher = Hasher::new();
+    hasher.update(path_bytes);
+    hasher.update(&data);
+    hasher.update(&[flags]);
+
+    Ok(Record { path, data, flags, checksum_ok: hasher.finalize() == checksum })
+}
+
+fn read_u32(bytes: &[u8], offset: &mut usize) -> Result<u32> {
+    let slice = read_slice(bytes, offset, 4)?;
+    Ok(u32::from_le_bytes(slice.try_into().expect("read_slice returns exactly 4 bytes")))
+}
+
+fn read_slice<'a>(bytes: &'a [u8], offset: &mut usize, len: usize) -> Result<&'a [u8]> {
+    let end = offset.checked_add(len).ok_or_else(|| FileSystemError::Corrupted("<record>".to_string()))?;
+    let slice = bytes.get(*offset..end).ok_or_else(|| FileSystemError::Corrupted("<record>".to_string()))?;
+    *offset = end;
+    Ok(slice)
+}
@@ -0,0 +1,98 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use futures_core::Stream;
+use tokio::sync::RwLock;
+
+use crate::{error::FileSystemError, file::File, node::Dir, resolve_file_in, Result};
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+
+enum State {
+    Invalid(FileSystemError),
+    Resolving(BoxFuture<Result<Arc<RwLock<File>>>>),
+    Reading { file: Arc<RwLock<File>>, fut: BoxFuture<(usize, Vec<u8>)> },
+    Done,
+}
+
+/// A stream of fixed-size chunks read from a file, returned by
+/// [`crate::FileSystem::read_stream`].
+pub(crate) struct FileStream {
+    offset: usize,
+    size: usize,
+    chunk_size: usize,
+    state: State,
+}
+
+impl FileStream {
+    pub(crate) fn new(root: Arc<RwLock<Dir>>, path: String, chunk_size: usize) -> Self {
+        let state = if chunk_size == 0 {
+            State::Invalid(FileSystemError::ReadError("chunk_size must be greater than zero".to_string()))
+        } else {
+            State::Resolving(Box::pin(async move { resolve_file_in(root, &path).await }))
+        };
+        Self { offset: 0, size: 0, chunk_size, state }
+    }
+
+    fn read_chunk(file: Arc<RwLock<File>>, offset: usize, chunk_size: usize) -> BoxFuture<(usize, Vec<u8>)> {
+        Box::pin(async move {
+            let file = file.read().await;
+            (file.len(), file.read(offset, chunk_size))
+        })
+    }
+}
+
+impl Stream for FileStream {
+    type Item = Result<Vec<u8>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            match &mut this.state {
+                State::Invalid(err) => {
+                    let err = err.clone();
+                    this.state = State::Done;
+                    return Poll::Ready(Some(Err(err)));
+                }
+                State::Resolving(fut) => {
+                    let file = match fut.as_mut().poll(cx) {
+                        Poll::Ready(Ok(file)) => file,
+                        Poll::Ready(Err(err)) => {
+                            this.state = State::Done;
+                            return Poll::Ready(Some(Err(err)));
+                        }
+                        Poll::Pending => return Poll::Pending,
+                    };
+                    this.state = State::Reading {
+                        fut: Self::read_chunk(file.clone(), this.offset, this.chunk_size),
+                        file,
+                    };
+                }
+                State::Reading { file, fut } => {
+                    let (size, chunk) = match fut.as_mut().poll(cx) {
+                        Poll::Ready(result) => result,
+                        Poll::Pending => return Poll::Pending,
+                    };
+                    this.size = size;
+
+                    if this.offset >= this.size {
+                        this.state = State::Done;
+                        return Poll::Ready(None);
+                    }
+
+                    this.offset += chunk.len();
+                    this.state = State::Reading {
+                        fut: Self::read_chunk(file.clone(), this.offset, this.chunk_size),
+                        file: file.clone(),
+                    };
+                    return Poll::Ready(Some(Ok(chunk)));
+                }
+                State::Done => return Poll::Ready(None),
+            }
+        }
+    }
+}
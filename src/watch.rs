@@ -0,0 +1,48 @@
+use std::collections::HashSet;
+
+/// The kind of mutation that produced a [`ChangeEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeKind {
+    /// An entry was created.
+    Create,
+    /// A file's contents were modified.
+    Modify,
+    /// An entry was removed.
+    Delete,
+    /// An entry was moved to a new path.
+    Rename,
+}
+
+/// A filter over [`ChangeKind`]s, used by [`crate::FileSystem::watch`] to
+/// select which mutations a watcher is notified about.
+#[derive(Debug, Clone, Default)]
+pub struct ChangeKindSet(HashSet<ChangeKind>);
+
+impl ChangeKindSet {
+    /// Returns an empty set, matching no [`ChangeKind`].
+    pub fn new() -> Self { Self::default() }
+
+    /// Returns a set matching every [`ChangeKind`].
+    pub fn all() -> Self {
+        Self(HashSet::from([ChangeKind::Create, ChangeKind::Modify, ChangeKind::Delete, ChangeKind::Rename]))
+    }
+
+    /// Adds `kind` to the set.
+    pub fn with(mut self, kind: ChangeKind) -> Self {
+        self.0.insert(kind);
+        self
+    }
+
+    /// Returns whether `kind` is part of the set.
+    pub fn contains(&self, kind: ChangeKind) -> bool { self.0.contains(&kind) }
+}
+
+/// An event describing a single mutation to the file system, broadcast by
+/// [`crate::FileSystem::watch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangeEvent {
+    /// The path that was mutated.
+    pub path: String,
+    /// What kind of mutation occurred.
+    pub kind: ChangeKind,
+}